@@ -75,8 +75,11 @@
 //! * `--(#=type)--`: the hash of the value following `->` must match to the value of the `<type>`
 //!
 
-use bitcoin::{hash_types::*, blockdata::script::*, secp256k1};
-use miniscript::{Miniscript, MiniscriptKey, miniscript::iter::PubkeyOrHash};
+use std::convert::TryFrom;
+use bitcoin::{hash_types::*, blockdata::script::*, blockdata::opcodes, secp256k1, Address, Network};
+use bitcoin::hashes::{Hash, HashEngine, sha256};
+use bitcoin::util::address::Payload;
+use miniscript::{Descriptor, Miniscript, MiniscriptKey, descriptor::DescriptorType, miniscript::iter::PubkeyOrHash};
 use crate::Wrapper;
 
 
@@ -135,18 +138,86 @@ impl LockScript {
             })?;
         Ok(LockScript::from_inner(result.encode()))
     }
+
+    /// Encodes a miniscript output [`Descriptor`]'s explicit script as a [`LockScript`].
+    ///
+    /// Returns [`LockScriptFromDescriptorError::NoExplicitScript`] for descriptors that have no
+    /// single explicit script, such as `Descriptor::Tr` - Taproot descriptors aren't supported
+    /// through this function at all, since there is no single `LockScript` to hand to
+    /// [`ScriptSet::from_lock_script`] for them; derive their `scriptPubkey` directly via
+    /// [`PubkeyScriptType::p2tr`] or [`PubkeyScriptType::p2tr_script_path`] instead.
+    pub fn from_descriptor(
+        descriptor: &Descriptor<bitcoin::PublicKey>
+    ) -> Result<LockScript, LockScriptFromDescriptorError> {
+        if let Descriptor::Tr(_) = descriptor {
+            return Err(LockScriptFromDescriptorError::NoExplicitScript);
+        }
+        Ok(LockScript::from_inner(descriptor.explicit_script()?))
+    }
+}
+
+#[derive(Debug)]
+pub enum LockScriptFromDescriptorError {
+    Miniscript(miniscript::Error),
+    NoExplicitScript,
+}
+
+impl From<miniscript::Error> for LockScriptFromDescriptorError {
+    fn from(miniscript_error: miniscript::Error) -> Self {
+        Self::Miniscript(miniscript_error)
+    }
+}
+
+/// A descriptor category that [`descriptor_category`] cannot express as a [`ConvertInfo`]
+/// strategy.
+#[derive(Debug)]
+pub enum DescriptorCategoryError {
+    /// `wpkh(...)`/`sh(wpkh(...))` commit directly to a public key hash: there is no script for
+    /// `ScriptSet::from_lock_script`'s `SegWitV0`/`SegWitScriptHash` strategies to hash, since
+    /// those strategies hash the `LockScript` itself into the witness/redeem script program.
+    /// `LockScript::from_descriptor`'s `explicit_script()` for these descriptors is the signing
+    /// script code, not the thing committed to by `scriptPubkey`, so routing it through
+    /// `ConvertInfo::SegWitV0`/`SegWitScriptHash` would silently produce the wrong output.
+    KeyHashDescriptor,
+}
+
+/// Maps an output [`Descriptor`]'s [`DescriptorType`] onto the [`ConvertInfo`] strategy used to
+/// embed a [`LockScript`] into the matching `scriptPubkey`/`sigScript`/`witness` triple.
+///
+/// Returns [`DescriptorCategoryError::KeyHashDescriptor`] for `wpkh(...)`/`sh(wpkh(...))`, which
+/// aren't representable as a `LockScript`-hashing strategy; derive their `scriptPubkey` directly
+/// from the key instead (`Builder::gen_v0_p2wpkh` over the key's `WPubkeyHash`, wrapped with
+/// `gen_p2sh` over the `ScriptHash` of that v0 program for `sh(wpkh(...))`).
+pub fn descriptor_category(
+    descriptor: &Descriptor<bitcoin::PublicKey>
+) -> Result<ConvertInfo, DescriptorCategoryError> {
+    match descriptor.desc_type() {
+        DescriptorType::Bare | DescriptorType::Pkh => Ok(ConvertInfo::Bare),
+        DescriptorType::Sh | DescriptorType::ShSortedMulti => Ok(ConvertInfo::Hashed),
+        DescriptorType::Wsh | DescriptorType::WshSortedMulti => Ok(ConvertInfo::SegWitV0),
+        DescriptorType::ShWsh | DescriptorType::ShWshSortedMulti =>
+            Ok(ConvertInfo::SegWitScriptHash),
+        DescriptorType::Wpkh | DescriptorType::ShWpkh => Err(DescriptorCategoryError::KeyHashDescriptor),
+        DescriptorType::Tr => Ok(ConvertInfo::Taproot),
+    }
 }
 
 
 pub enum PubkeyScriptType {
     P2S(PubkeyScript),
-    P2PK(bitcoin::PublicKey),
+    /// Raw 33- or 65-byte pubkey push from a `P2PK` template. Kept as raw bytes rather than a
+    /// parsed `bitcoin::PublicKey` because the template match (push of that length + `OP_CHECKSIG`)
+    /// doesn't guarantee the pushed bytes are a valid curve point.
+    P2PK(Vec<u8>),
     P2PKH(PubkeyHash),
     P2SH(ScriptHash),
     P2OR(Vec<u8>),
     P2WPKH(WPubkeyHash),
     P2WSH(WScriptHash),
-    P2TR(secp256k1::PublicKey),
+    /// Raw 32-byte x-only output key from a `P2TR` template. Kept as raw bytes rather than a
+    /// parsed `secp256k1::PublicKey` because a witness v1 program is standard-looking regardless
+    /// of whether those 32 bytes happen to be a valid x-coordinate.
+    P2TR([u8; 32]),
 }
 
 pub enum PubkeyScriptSource {
@@ -155,14 +226,86 @@ pub enum PubkeyScriptSource {
     P2PKH(bitcoin::PublicKey),
     P2SH(LockScript),
     P2OR(Vec<u8>),
-    P2WPKH(LockScript),
+    P2WPKH(bitcoin::PublicKey),
     P2WSH(LockScript),
     P2TR(bitcoin::PublicKey, TapScript),
 }
 
+impl From<PubkeyScriptSource> for PubkeyScriptType {
+    /// Derives the committed-to [`PubkeyScriptType`] from the raw key/script material a
+    /// `scriptPubkey` would be built from - the step before `From<PubkeyScriptType> for
+    /// PubkeyScript`. `P2TR` computes the full BIP-341 key-and-script-path
+    /// tweak via [`PubkeyScriptType::p2tr_script_path`], using the leaf's own `TapLeaf` hash as
+    /// the (single-leaf) merkle root.
+    fn from(source: PubkeyScriptSource) -> Self {
+        use PubkeyScriptSource::*;
+        match source {
+            P2S(script) => Self::P2S(PubkeyScript::from_inner(script.into_inner())),
+            P2PK(pubkey) => Self::P2PK(pubkey.to_bytes()),
+            P2PKH(pubkey) => Self::P2PKH(pubkey.pubkey_hash()),
+            P2SH(script) => Self::P2SH(ScriptHash::hash(&script.into_inner()[..])),
+            P2OR(data) => Self::P2OR(data),
+            P2WPKH(pubkey) => Self::P2WPKH(
+                pubkey.wpubkey_hash().expect("P2WPKH requires a compressed public key")
+            ),
+            P2WSH(script) => Self::P2WSH(WScriptHash::hash(&script.into_inner()[..])),
+            P2TR(internal_key, leaf) => Self::p2tr_script_path(internal_key, leaf),
+        }
+    }
+}
+
 impl From<Script> for PubkeyScriptType {
     fn from(script_pubkey: Script) -> Self {
-        Self::P2S(PubkeyScript::from_inner(script_pubkey))
+        Self::classify(&PubkeyScript::from_inner(script_pubkey))
+    }
+}
+
+impl PubkeyScriptType {
+    /// Structurally classifies an arbitrary `scriptPubkey`, recognizing the standard output
+    /// forms (`P2PKH`, `P2SH`, `P2WPKH`, `P2WSH`, `P2TR`, `P2PK`, `P2OR`) and falling back to
+    /// [`PubkeyScriptType::P2S`] for anything that doesn't match a known template. This is the
+    /// inverse of `From<PubkeyScriptType> for PubkeyScript`.
+    pub fn classify(script: &PubkeyScript) -> Self {
+        match script.as_inner().as_bytes() {
+            [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 =>
+                Self::P2PKH(PubkeyHash::from_slice(hash).expect("20-byte slice")),
+
+            [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 =>
+                Self::P2SH(ScriptHash::from_slice(hash).expect("20-byte slice")),
+
+            [0x00, 0x14, hash @ ..] if hash.len() == 20 =>
+                Self::P2WPKH(WPubkeyHash::from_slice(hash).expect("20-byte slice")),
+
+            [0x00, 0x20, hash @ ..] if hash.len() == 32 =>
+                Self::P2WSH(WScriptHash::from_slice(hash).expect("32-byte slice")),
+
+            [0x51, 0x20, xonly @ ..] if xonly.len() == 32 => {
+                let mut program = [0u8; 32];
+                program.copy_from_slice(xonly);
+                Self::P2TR(program)
+            },
+
+            [0x21, pubkey @ .., 0xac] if pubkey.len() == 33 =>
+                Self::P2PK(pubkey.to_vec()),
+
+            [0x41, pubkey @ .., 0xac] if pubkey.len() == 65 =>
+                Self::P2PK(pubkey.to_vec()),
+
+            [0x6a, ..] => {
+                // Strip the push opcode/length prefix rather than keeping it as part of the
+                // payload, so this round-trips through `Builder::gen_op_return`'s proper push.
+                let data = script.as_inner().instructions().nth(1)
+                    .and_then(Result::ok)
+                    .and_then(|instruction| match instruction {
+                        Instruction::PushBytes(bytes) => Some(bytes.to_vec()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                Self::P2OR(data)
+            },
+
+            _ => Self::P2S(script.clone()),
+        }
     }
 }
 
@@ -173,13 +316,576 @@ impl From<PubkeyScriptType> for PubkeyScript {
         PubkeyScript::from_inner(match spkt {
             P2S(script) => script.into_inner(),
             P2PK(pubkey) =>
-                Builder::gen_p2pk(&pubkey).into_script(),
+                Builder::new().push_slice(&pubkey).push_opcode(opcodes::all::OP_CHECKSIG).into_script(),
             P2PKH(pubkey_hash) => Builder::gen_p2pkh(&pubkey_hash).into_script(),
             P2SH(script_hash) => Builder::gen_p2sh(&script_hash).into_script(),
             P2OR(data) => Builder::gen_op_return(&data).into_script(),
             P2WPKH(wpubkey_hash) => Builder::gen_v0_p2wpkh(&wpubkey_hash).into_script(),
             P2WSH(wscript_hash) => Builder::gen_v0_p2wsh(&wscript_hash).into_script(),
-            P2TR(pubkey) => unimplemented!(),
+            P2TR(program) => Builder::new().push_int(1).push_slice(&program).into_script(),
         })
     }
-}
\ No newline at end of file
+}
+
+
+// ====================================================== Taproot (BIP-341/BIP-342) ===
+
+/// Leaf version for the only tapscript flavour defined so far, per BIP-342.
+pub const TAPROOT_LEAF_TAPSCRIPT: u8 = 0xc0;
+
+fn tagged_hash(tag: &str, msg: &[u8]) -> sha256::Hash {
+    let tag_hash = sha256::Hash::hash(tag.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag_hash[..]);
+    engine.input(&tag_hash[..]);
+    engine.input(msg);
+    sha256::Hash::from_engine(engine)
+}
+
+fn to_xonly(pubkey: &secp256k1::PublicKey) -> [u8; 32] {
+    let mut xonly = [0u8; 32];
+    xonly.copy_from_slice(&pubkey.serialize()[1..]);
+    xonly
+}
+
+// BIP-340/341 require the internal key to be the point with an even y-coordinate for the given
+// x-coordinate; a key supplied with odd y is negated to its even-y counterpart before tweaking.
+fn lift_x(
+    secp: &secp256k1::Secp256k1<impl secp256k1::Verification>,
+    pubkey: secp256k1::PublicKey,
+) -> secp256k1::PublicKey {
+    if pubkey.serialize()[0] == 0x02 {
+        pubkey
+    } else {
+        let mut pubkey = pubkey;
+        pubkey.negate_assign(secp);
+        pubkey
+    }
+}
+
+fn tap_branch_hash(a: sha256::Hash, b: sha256::Hash) -> sha256::Hash {
+    let mut msg = Vec::with_capacity(64);
+    if a[..] < b[..] {
+        msg.extend(&a[..]);
+        msg.extend(&b[..]);
+    } else {
+        msg.extend(&b[..]);
+        msg.extend(&a[..]);
+    }
+    tagged_hash("TapBranch", &msg)
+}
+
+/// Applies the BIP-341 output key tweak `Q = P + hash_TapTweak(P || m)·G` to an `internal_key`,
+/// returning the resulting output key together with its y-coordinate parity. `merkle_root` is
+/// `None` for a pure key-path spend and `Some` of the script tree's root for a key committing to
+/// one or more `TapScript` leaves.
+pub fn taproot_tweak_pubkey(
+    internal_key: secp256k1::PublicKey,
+    merkle_root: Option<sha256::Hash>,
+) -> (secp256k1::PublicKey, bool) {
+    let secp = secp256k1::Secp256k1::verification_only();
+    let internal_key = lift_x(&secp, internal_key);
+
+    let mut msg = to_xonly(&internal_key).to_vec();
+    if let Some(root) = merkle_root {
+        msg.extend(&root[..]);
+    }
+    let tweak = tagged_hash("TapTweak", &msg);
+
+    let mut output_key = internal_key;
+    output_key
+        .add_exp_assign(&secp, &tweak[..])
+        .expect("TapTweak hash is a valid scalar with overwhelming probability");
+    let parity = output_key.serialize()[0] == 0x03;
+
+    (output_key, parity)
+}
+
+impl TapScript {
+    /// Computes the `TapLeaf` hash of this script under the default tapscript leaf version
+    /// `0xc0` defined in BIP-342.
+    pub fn tap_leaf_hash(&self) -> sha256::Hash {
+        let mut msg = vec![TAPROOT_LEAF_TAPSCRIPT];
+        msg.extend(bitcoin::consensus::encode::serialize(&self.clone().into_inner()));
+        tagged_hash("TapLeaf", &msg)
+    }
+
+    /// Builds the BIP-341 control block proving that this script is committed to by
+    /// `internal_key` along the given merkle `path` of sibling hashes, ordered from the leaf
+    /// upwards towards the root.
+    pub fn control_block(&self, internal_key: secp256k1::PublicKey, path: &[sha256::Hash]) -> Vec<u8> {
+        let merkle_root = path.iter()
+            .fold(self.tap_leaf_hash(), |node, sibling| tap_branch_hash(node, *sibling));
+        let (_, parity) = taproot_tweak_pubkey(internal_key, Some(merkle_root));
+
+        let mut control_block = Vec::with_capacity(33 + 32 * path.len());
+        control_block.push(TAPROOT_LEAF_TAPSCRIPT | parity as u8);
+        control_block.extend(&to_xonly(&internal_key));
+        for sibling in path {
+            control_block.extend(&sibling[..]);
+        }
+        control_block
+    }
+}
+
+impl PubkeyScriptType {
+    /// Derives the `P2TR` output key variant from an `internal_key`, tweaking it for a
+    /// key-path-only spend (`merkle_root = None`) or for a key committing to a script tree
+    /// rooted at `merkle_root`.
+    pub fn p2tr(internal_key: secp256k1::PublicKey, merkle_root: Option<sha256::Hash>) -> Self {
+        let (output_key, _) = taproot_tweak_pubkey(internal_key, merkle_root);
+        Self::P2TR(to_xonly(&output_key))
+    }
+
+    /// Derives the `P2TR` output key variant for a single-leaf script tree, as carried by
+    /// [`PubkeyScriptSource::P2TR`], using the leaf's own `TapLeaf` hash as the merkle root.
+    pub fn p2tr_script_path(internal_key: bitcoin::PublicKey, leaf: TapScript) -> Self {
+        Self::p2tr(internal_key.key, Some(leaf.tap_leaf_hash()))
+    }
+}
+
+/// BIP-341 "nothing-up-my-sleeve" internal key: `lift_x(0x50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0)`.
+/// Used to build a `P2TR` output that commits to a script tree without enabling a key-path spend.
+pub fn unspendable_internal_key() -> secp256k1::PublicKey {
+    secp256k1::PublicKey::from_slice(&[
+        0x02, 0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9,
+        0x7a, 0x5e, 0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce,
+        0x80, 0x3a, 0xc0,
+    ]).expect("hardcoded BIP-341 NUMS point is a valid compressed public key")
+}
+
+
+// ====================================================== Script conversion strategies ===
+
+/// A strategy for deriving `scriptPubkey` and the matching input-side spending data from a
+/// single [`LockScript`], mirroring the output descriptor categories used elsewhere in the
+/// ecosystem (bare, P2SH, P2WSH, P2SH-P2WSH and Taproot).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConvertInfo {
+    /// Lock script is used as `scriptPubkey` with no embedding or hashing (P2PK and custom
+    /// non-standard outputs).
+    Bare,
+    /// Lock script is committed into `scriptPubkey` as a `P2SH` hash, with the original script
+    /// carried as `redeemScript` inside `sigScript`.
+    Hashed,
+    /// Lock script is committed into `scriptPubkey` as a `P2WSH` v0 witness program, nested
+    /// inside a `P2SH` `redeemScript` (P2WSH-in-P2SH).
+    SegWitScriptHash,
+    /// Lock script is committed into `scriptPubkey` as a bare `P2WSH` v0 witness program, with
+    /// the original script carried in the witness stack.
+    SegWitV0,
+    /// Lock script is committed into `scriptPubkey` as a `P2TR` output, using the BIP-341
+    /// unspendable internal key since no key-path spend is available from a lock script alone.
+    Taproot,
+}
+
+/// The complete output-side and input-side spending data derived from a [`LockScript`] for a
+/// given [`ConvertInfo`] strategy: what goes into `scriptPubkey`, and what a spender must put
+/// into `sigScript` and `witness` to satisfy it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScriptSet {
+    pub pubkey_script: PubkeyScript,
+    pub sig_script: SigScript,
+    pub witness: Option<Witness>,
+}
+
+impl ScriptSet {
+    /// Derives the complete [`ScriptSet`] for spending `lock_script` under the given
+    /// [`ConvertInfo`] strategy.
+    pub fn from_lock_script(lock_script: &LockScript, strategy: ConvertInfo) -> ScriptSet {
+        let script = lock_script.clone().into_inner();
+
+        match strategy {
+            ConvertInfo::Bare => ScriptSet {
+                pubkey_script: PubkeyScript::from_inner(script),
+                sig_script: SigScript::from_inner(Script::new()),
+                witness: None,
+            },
+
+            ConvertInfo::Hashed => {
+                let script_hash = ScriptHash::hash(&script[..]);
+                ScriptSet {
+                    pubkey_script: PubkeyScript::from_inner(Builder::gen_p2sh(&script_hash).into_script()),
+                    sig_script: SigScript::from_inner(Builder::new().push_slice(&script[..]).into_script()),
+                    witness: None,
+                }
+            },
+
+            ConvertInfo::SegWitV0 => {
+                let wscript_hash = WScriptHash::hash(&script[..]);
+                ScriptSet {
+                    pubkey_script: PubkeyScript::from_inner(Builder::gen_v0_p2wsh(&wscript_hash).into_script()),
+                    sig_script: SigScript::from_inner(Script::new()),
+                    witness: Some(Witness::from(vec![script])),
+                }
+            },
+
+            ConvertInfo::SegWitScriptHash => {
+                let wscript_hash = WScriptHash::hash(&script[..]);
+                let witness_program = Builder::gen_v0_p2wsh(&wscript_hash).into_script();
+                let script_hash = ScriptHash::hash(&witness_program[..]);
+                ScriptSet {
+                    pubkey_script: PubkeyScript::from_inner(Builder::gen_p2sh(&script_hash).into_script()),
+                    sig_script: SigScript::from_inner(Builder::new().push_slice(&witness_program[..]).into_script()),
+                    witness: Some(Witness::from(vec![script])),
+                }
+            },
+
+            ConvertInfo::Taproot => {
+                let internal_key = unspendable_internal_key();
+                let tap_script = TapScript::from_inner(script);
+                let control_block = tap_script.control_block(internal_key, &[]);
+                let pubkey_script = PubkeyScriptType::p2tr_script_path(
+                    bitcoin::PublicKey { compressed: true, key: internal_key },
+                    tap_script.clone(),
+                ).into();
+                ScriptSet {
+                    pubkey_script,
+                    sig_script: SigScript::from_inner(Script::new()),
+                    witness: Some(Witness::from(vec![tap_script.into_inner().to_bytes(), control_block])),
+                }
+            },
+        }
+    }
+}
+
+
+// ====================================================== Witness (BIP-141) ===
+
+/// The `witness` field of a transaction input (BIP-141): an ordered stack of byte-string items.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Witness(Vec<Vec<u8>>);
+
+impl Witness {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, item: impl Into<Vec<u8>>) {
+        self.0.push(item.into());
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<Vec<u8>> {
+        self.0.iter()
+    }
+
+    pub fn as_stack(&self) -> &[Vec<u8>] {
+        &self.0
+    }
+}
+
+impl From<Vec<Vec<u8>>> for Witness {
+    fn from(stack: Vec<Vec<u8>>) -> Self {
+        Self(stack)
+    }
+}
+
+impl WitnessScript {
+    /// Packages this witness script together with its `satisfaction` (signatures and other
+    /// witness-stack arguments, bottom-to-top) into a complete [`Witness`], appending the
+    /// script itself as the final stack item per BIP-141.
+    pub fn to_witness(&self, satisfaction: impl IntoIterator<Item = Vec<u8>>) -> Witness {
+        let mut stack: Vec<Vec<u8>> = satisfaction.into_iter().collect();
+        stack.push(self.clone().into_inner().to_bytes());
+        Witness(stack)
+    }
+}
+
+/// A `scriptPubkey` byte pattern that doesn't correspond to any BIP-141 witness program.
+#[derive(Debug)]
+pub struct WitnessProgramError;
+
+/// The leading witness version opcode of a segregated witness program, per BIP-141/BIP-341:
+/// `OP_0` for v0 (P2WPKH/P2WSH) and `OP_1`..`OP_16` for v1 (Taproot) through v16.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WitnessVersion {
+    V0 = 0, V1 = 1, V2 = 2, V3 = 3, V4 = 4, V5 = 5, V6 = 6, V7 = 7, V8 = 8,
+    V9 = 9, V10 = 10, V11 = 11, V12 = 12, V13 = 13, V14 = 14, V15 = 15, V16 = 16,
+}
+
+impl WitnessVersion {
+    /// Parses a witness version from the leading opcode of a `scriptPubkey`: `OP_0` (`0x00`) or
+    /// `OP_1`..`OP_16` (`0x51`..`0x60`).
+    pub fn from_opcode(opcode: u8) -> Result<Self, WitnessProgramError> {
+        use WitnessVersion::*;
+        Ok(match opcode {
+            0x00 => V0,
+            0x51 => V1, 0x52 => V2, 0x53 => V3, 0x54 => V4,
+            0x55 => V5, 0x56 => V6, 0x57 => V7, 0x58 => V8,
+            0x59 => V9, 0x5a => V10, 0x5b => V11, 0x5c => V12,
+            0x5d => V13, 0x5e => V14, 0x5f => V15, 0x60 => V16,
+            _ => return Err(WitnessProgramError),
+        })
+    }
+
+    pub fn to_num(self) -> u8 {
+        self as u8
+    }
+}
+
+/// A BIP-141/BIP-341 witness program: a version (0–16) and a 2–40 byte program, as carried by a
+/// `scriptPubkey` of the form `<version> <program>`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WitnessProgram {
+    version: WitnessVersion,
+    program: Vec<u8>,
+}
+
+impl WitnessProgram {
+    pub fn new(version: WitnessVersion, program: Vec<u8>) -> Result<Self, WitnessProgramError> {
+        if program.len() < 2 || program.len() > 40 {
+            return Err(WitnessProgramError);
+        }
+        Ok(Self { version, program })
+    }
+
+    pub fn version(&self) -> WitnessVersion {
+        self.version
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.program
+    }
+}
+
+impl TryFrom<&PubkeyScript> for WitnessProgram {
+    type Error = WitnessProgramError;
+
+    fn try_from(script: &PubkeyScript) -> Result<Self, Self::Error> {
+        let bytes = script.as_inner().as_bytes();
+        if bytes.len() < 4 || bytes.len() > 42 {
+            return Err(WitnessProgramError);
+        }
+
+        let version = WitnessVersion::from_opcode(bytes[0])?;
+        let push_len = bytes[1] as usize;
+        if push_len != bytes.len() - 2 {
+            return Err(WitnessProgramError);
+        }
+
+        WitnessProgram::new(version, bytes[2..].to_vec())
+    }
+}
+
+
+// ====================================================== Address derivation ===
+
+impl PubkeyScript {
+    /// Derives the standard address for this `scriptPubkey` on the given `network`, or `None`
+    /// if it doesn't correspond to any standard output form.
+    ///
+    /// Legacy `P2PKH`/`P2SH` scripts become base58 addresses. Witness outputs are read out as a
+    /// [`WitnessProgram`] and placed straight into [`Payload::WitnessProgram`] with their actual
+    /// version, rather than going through `bitcoin::Address`'s fixed-version convenience
+    /// constructors (which historically only cover v0) - so v0-v16 programs, Taproot included,
+    /// are carried into the address with their actual version and program bytes. The choice of
+    /// bech32 or bech32m for the address string is left to `bitcoin::Address`. The `WitnessProgram` parse
+    /// is attempted directly rather than gated on `PubkeyScriptType::classify`, which only
+    /// recognizes the standard v0/v1 templates and would otherwise drop v2-v16 programs into the
+    /// `P2S` catch-all.
+    pub fn to_address(&self, network: Network) -> Option<Address> {
+        match PubkeyScriptType::classify(self) {
+            PubkeyScriptType::P2PKH(hash) => Some(Address { network, payload: Payload::PubkeyHash(hash) }),
+            PubkeyScriptType::P2SH(hash) => Some(Address { network, payload: Payload::ScriptHash(hash) }),
+            _ => {
+                let program = WitnessProgram::try_from(self).ok()?;
+                let version = bitcoin::bech32::u5::try_from_u8(program.version().to_num()).ok()?;
+                Some(Address {
+                    network,
+                    payload: Payload::WitnessProgram { version, program: program.program().to_vec() },
+                })
+            },
+        }
+    }
+
+    /// Recovers the `scriptPubkey` committed to by `address`.
+    pub fn from_address(address: Address) -> PubkeyScript {
+        PubkeyScript::from_inner(address.script_pubkey())
+    }
+}
+
+
+#[cfg(test)]
+mod taproot_tests {
+    use super::*;
+    use bitcoin::hashes::hex::FromHex;
+
+    // Internal key = negation of the secp256k1 generator point G (private key n-1), i.e. G's
+    // x-coordinate with odd y, so these vectors also exercise `lift_x`'s negate-to-even-y branch.
+    const INTERNAL_KEY: &str =
+        "0379be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const INTERNAL_XONLY: &str =
+        "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    fn internal_key() -> secp256k1::PublicKey {
+        secp256k1::PublicKey::from_slice(&Vec::from_hex(INTERNAL_KEY).unwrap()).unwrap()
+    }
+
+    // Single-leaf tapscript `OP_1`, matching the leaf/tweak/control-block math independently
+    // recomputed against the BIP-340/341 tagged-hash and point-tweak formulas.
+    fn leaf_script() -> TapScript {
+        TapScript::from_inner(Builder::new().push_int(1).into_script())
+    }
+
+    #[test]
+    fn tap_leaf_hash_matches_tagged_hash_formula() {
+        let leaf_hash = leaf_script().tap_leaf_hash();
+        assert_eq!(
+            leaf_hash,
+            sha256::Hash::from_slice(&Vec::from_hex(
+                "a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675"
+            ).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn taproot_tweak_pubkey_key_path_lifts_odd_y_internal_key() {
+        let (output_key, parity) = taproot_tweak_pubkey(internal_key(), None);
+        assert_eq!(
+            to_xonly(&output_key)[..],
+            Vec::from_hex("da4710964f7852695de2da025290e24af6d8c281de5a0b902b7135fd9fd74d21")
+                .unwrap()[..]
+        );
+        assert!(parity);
+    }
+
+    #[test]
+    fn taproot_tweak_pubkey_script_path_commits_to_leaf() {
+        let merkle_root = leaf_script().tap_leaf_hash();
+        let (output_key, parity) = taproot_tweak_pubkey(internal_key(), Some(merkle_root));
+        assert_eq!(
+            to_xonly(&output_key)[..],
+            Vec::from_hex("9b6ce0db0707e29f92bf8893ed1911d397e3d2d76bbc68110c49da2ceec8be23")
+                .unwrap()[..]
+        );
+        assert!(!parity);
+    }
+
+    #[test]
+    fn control_block_with_no_siblings_is_version_parity_and_internal_key() {
+        let control_block = leaf_script().control_block(internal_key(), &[]);
+        let mut expected = vec![TAPROOT_LEAF_TAPSCRIPT];
+        expected.extend(Vec::from_hex(INTERNAL_XONLY).unwrap());
+        assert_eq!(control_block, expected);
+    }
+
+    #[test]
+    fn tap_branch_hash_is_independent_of_sibling_order() {
+        let a = sha256::Hash::from_inner([0u8; 32]);
+        let mut b_bytes = [0u8; 32];
+        b_bytes[0] = 1;
+        let b = sha256::Hash::from_inner(b_bytes);
+        assert_eq!(tap_branch_hash(a, b), tap_branch_hash(b, a));
+    }
+}
+
+
+#[cfg(test)]
+mod classify_tests {
+    use super::*;
+
+    fn pubkey_script(script: Script) -> PubkeyScript {
+        PubkeyScript::from_inner(script)
+    }
+
+    #[test]
+    fn classifies_p2pkh() {
+        let hash = PubkeyHash::hash(&[0x02; 33]);
+        let script = pubkey_script(Builder::gen_p2pkh(&hash).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2PKH(h) => assert_eq!(h, hash),
+            _ => panic!("expected P2PKH"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2sh() {
+        let hash = ScriptHash::hash(&[0x51]);
+        let script = pubkey_script(Builder::gen_p2sh(&hash).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2SH(h) => assert_eq!(h, hash),
+            _ => panic!("expected P2SH"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2wpkh() {
+        let hash = WPubkeyHash::hash(&[0x02; 33]);
+        let script = pubkey_script(Builder::gen_v0_p2wpkh(&hash).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2WPKH(h) => assert_eq!(h, hash),
+            _ => panic!("expected P2WPKH"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2wsh() {
+        let hash = WScriptHash::hash(&[0x51]);
+        let script = pubkey_script(Builder::gen_v0_p2wsh(&hash).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2WSH(h) => assert_eq!(h, hash),
+            _ => panic!("expected P2WSH"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2tr() {
+        let program = [0x42u8; 32];
+        let script = pubkey_script(Builder::new().push_int(1).push_slice(&program).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2TR(xonly) => assert_eq!(xonly, program),
+            _ => panic!("expected P2TR"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2pk_compressed() {
+        let pubkey = [0x02u8; 33];
+        let script = pubkey_script(
+            Builder::new().push_slice(&pubkey).push_opcode(opcodes::all::OP_CHECKSIG).into_script()
+        );
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2PK(key) => assert_eq!(key, pubkey.to_vec()),
+            _ => panic!("expected P2PK"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2pk_uncompressed() {
+        let pubkey = [0x04u8; 65];
+        let script = pubkey_script(
+            Builder::new().push_slice(&pubkey).push_opcode(opcodes::all::OP_CHECKSIG).into_script()
+        );
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2PK(key) => assert_eq!(key, pubkey.to_vec()),
+            _ => panic!("expected P2PK"),
+        }
+    }
+
+    #[test]
+    fn classifies_p2or_and_strips_the_push_opcode() {
+        let data = vec![0xde, 0xad, 0xbe, 0xef];
+        let script = pubkey_script(Builder::gen_op_return(&data).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2OR(payload) => assert_eq!(payload, data),
+            _ => panic!("expected P2OR"),
+        }
+    }
+
+    #[test]
+    fn p2or_round_trips_through_pubkey_script_conversion() {
+        let data = vec![0xde, 0xad, 0xbe, 0xef];
+        let script = pubkey_script(Builder::gen_op_return(&data).into_script());
+        let rebuilt: PubkeyScript = PubkeyScriptType::classify(&script).into();
+        assert_eq!(rebuilt, script);
+    }
+
+    #[test]
+    fn classifies_non_standard_script_as_p2s() {
+        let script = pubkey_script(Builder::new().push_opcode(opcodes::all::OP_NOP).into_script());
+        match PubkeyScriptType::classify(&script) {
+            PubkeyScriptType::P2S(s) => assert_eq!(s, script),
+            _ => panic!("expected P2S"),
+        }
+    }
+}